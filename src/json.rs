@@ -2,17 +2,25 @@ use std::collections::HashMap;
 
 use self::lexer::JsonLexer;
 
+mod decoder;
+mod encoder;
 mod lexer;
+mod streaming;
+
+pub use self::decoder::{decode, Decodable, Decoder, DecoderError};
+pub use self::streaming::{JsonEvent, StackElement, StreamingParser};
 
 pub type JsonStringType = String;
-pub type JsonNumberType = f32;
+pub type JsonNumberType = f64;
+pub type JsonIntegerType = i64;
 pub type JsonObjectType = HashMap<String, JsonValue>;
 pub type JsonArrayType = Vec<JsonValue>;
 pub type JsonBooleanType = bool;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum JsonValue {
     String(JsonStringType),
+    Integer(JsonIntegerType),
     Number(JsonNumberType),
     Object(JsonObjectType),
     Array(JsonArrayType),
@@ -20,6 +28,46 @@ pub enum JsonValue {
     Nil,
 }
 
+/// A position in the source text, one-based for lines and columns.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Position {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// The category of a [`ParseError`].
+#[derive(Debug, PartialEq)]
+pub enum ErrorKind {
+    UnexpectedToken,
+    InvalidNumber,
+    InvalidEscape,
+    EofWhileParsing,
+    TrailingCharacters,
+}
+
+/// A parse failure together with the location at which it occurred.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub col: usize,
+    pub kind: ErrorKind,
+}
+
+impl Position {
+    /// The position before any input has been read.
+    pub const START: Position = Position { line: 1, col: 0 };
+}
+
+impl ParseError {
+    pub fn new(pos: Position, kind: ErrorKind) -> Self {
+        ParseError {
+            line: pos.line,
+            col: pos.col,
+            kind,
+        }
+    }
+}
+
 pub struct JsonParser<'a> {
     lexer: JsonLexer<'a>,
     current: lexer::Token,
@@ -30,20 +78,25 @@ impl<'a> JsonParser<'a> {
     pub fn new(filename: String) -> Self {
         JsonParser {
             lexer: JsonLexer::new(filename),
-            current: lexer::Token(String::new(), lexer::JsonTokenType::None),
-            next: lexer::Token(String::new(), lexer::JsonTokenType::None),
+            current: lexer::Token(String::new(), lexer::JsonTokenType::None, Position::START),
+            next: lexer::Token(String::new(), lexer::JsonTokenType::None, Position::START),
         }
     }
 
     pub fn new_from_string(s: &'a String) -> Self {
         JsonParser {
             lexer: JsonLexer::new_from_string(s),
-            current: lexer::Token(String::new(), lexer::JsonTokenType::None),
-            next: lexer::Token(String::new(), lexer::JsonTokenType::None),
+            current: lexer::Token(String::new(), lexer::JsonTokenType::None, Position::START),
+            next: lexer::Token(String::new(), lexer::JsonTokenType::None, Position::START),
         }
     }
 
-    fn parse_value(&mut self) -> Result<JsonValue, String> {
+    /// Build an error anchored at the position of the lookahead token.
+    fn error(&self, kind: ErrorKind) -> ParseError {
+        ParseError::new(self.next.2, kind)
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, ParseError> {
         match self.peek().1 {
             lexer::JsonTokenType::String => self.parse_string(),
             lexer::JsonTokenType::Number => self.parse_number(),
@@ -56,12 +109,15 @@ impl<'a> JsonParser<'a> {
                 self.parse_array()
             }
             lexer::JsonTokenType::Boolean => self.parse_boolean(),
-            lexer::JsonTokenType::Null => Ok(JsonValue::Nil),
-            _ => Err("Unexpected input".to_string()),
+            lexer::JsonTokenType::Null => {
+                self.advance()?;
+                Ok(JsonValue::Nil)
+            }
+            _ => Err(self.error(ErrorKind::UnexpectedToken)),
         }
     }
 
-    fn parse_object(&mut self) -> Result<JsonValue, String> {
+    fn parse_object(&mut self) -> Result<JsonValue, ParseError> {
         let mut obj = JsonObjectType::new();
 
         if self.peek().1 == lexer::JsonTokenType::RightBrace {
@@ -71,10 +127,7 @@ impl<'a> JsonParser<'a> {
 
         loop {
             let key = self.parse_string()?;
-            self.consume(
-                lexer::JsonTokenType::Colon,
-                "Expected ':' after object key".to_string(),
-            )?;
+            self.consume(lexer::JsonTokenType::Colon, ErrorKind::UnexpectedToken)?;
 
             let value = self.parse_value()?;
             if let JsonValue::String(key) = key {
@@ -86,14 +139,14 @@ impl<'a> JsonParser<'a> {
             } else if self.try_match(lexer::JsonTokenType::RightBrace) {
                 break;
             } else {
-                return Err("Expected ',' or '}'".to_string());
+                return Err(self.error(ErrorKind::UnexpectedToken));
             }
         }
 
         Ok(JsonValue::Object(obj))
     }
 
-    fn parse_array(&mut self) -> Result<JsonValue, String> {
+    fn parse_array(&mut self) -> Result<JsonValue, ParseError> {
         let mut arr = JsonArrayType::new();
 
         if self.peek().1 == lexer::JsonTokenType::RightBracket {
@@ -110,39 +163,46 @@ impl<'a> JsonParser<'a> {
             } else if self.try_match(lexer::JsonTokenType::RightBracket) {
                 break;
             } else {
-                return Err("Expected ',' or ']'".to_string());
+                return Err(self.error(ErrorKind::UnexpectedToken));
             }
         }
 
         Ok(JsonValue::Array(arr))
     }
 
-    fn parse_string(&mut self) -> Result<JsonValue, String> {
+    fn parse_string(&mut self) -> Result<JsonValue, ParseError> {
         Ok(JsonValue::String(
-            self.consume(lexer::JsonTokenType::String, "Expected string".to_string())?
+            self.consume(lexer::JsonTokenType::String, ErrorKind::UnexpectedToken)?
                 .0
                 .clone(),
         ))
     }
 
-    fn parse_number(&mut self) -> Result<JsonValue, String> {
-        Ok(JsonValue::Number(
-            self.consume(lexer::JsonTokenType::Number, "Expected number".to_string())?
-                .0
-                .parse()
-                .unwrap(),
-        ))
+    fn parse_number(&mut self) -> Result<JsonValue, ParseError> {
+        let token = self.consume(lexer::JsonTokenType::Number, ErrorKind::UnexpectedToken)?;
+        let pos = token.2;
+        let text = token.0.clone();
+        let invalid = || ParseError::new(pos, ErrorKind::InvalidNumber);
+
+        // A fraction or exponent means the value is genuinely a float;
+        // otherwise keep it as an integer so ids and keys survive intact,
+        // falling back to a float only when it overflows the integer type.
+        if text.contains(['.', 'e', 'E']) {
+            text.parse().map(JsonValue::Number).map_err(|_| invalid())
+        } else {
+            match text.parse::<JsonIntegerType>() {
+                Ok(i) => Ok(JsonValue::Integer(i)),
+                Err(_) => text.parse().map(JsonValue::Number).map_err(|_| invalid()),
+            }
+        }
     }
 
-    fn parse_boolean(&mut self) -> Result<JsonValue, String> {
+    fn parse_boolean(&mut self) -> Result<JsonValue, ParseError> {
         Ok(JsonValue::Boolean(
-            self.consume(
-                lexer::JsonTokenType::Boolean,
-                "Expected boolean".to_string(),
-            )?
-            .0
-            .parse()
-            .unwrap(),
+            self.consume(lexer::JsonTokenType::Boolean, ErrorKind::UnexpectedToken)?
+                .0
+                .parse()
+                .unwrap(),
         ))
     }
 
@@ -150,12 +210,12 @@ impl<'a> JsonParser<'a> {
         &self.next
     }
 
-    fn advance(&mut self) -> Result<&lexer::Token, String> {
+    fn advance(&mut self) -> Result<&lexer::Token, ParseError> {
         if self.current.1 == lexer::JsonTokenType::EOF {
-            Err("unexpected EOF".to_string())
+            Err(ParseError::new(self.current.2, ErrorKind::EofWhileParsing))
         } else {
-            self.current = lexer::Token::new(self.next.0.clone(), self.next.1);
-            self.next = self.lexer.next_token().unwrap();
+            self.current = lexer::Token::new(self.next.0.clone(), self.next.1, self.next.2);
+            self.next = self.lexer.next_token()?;
             Ok(&self.current)
         }
     }
@@ -172,23 +232,26 @@ impl<'a> JsonParser<'a> {
     fn consume(
         &mut self,
         expected: lexer::JsonTokenType,
-        msg: String,
-    ) -> Result<&lexer::Token, String> {
+        kind: ErrorKind,
+    ) -> Result<&lexer::Token, ParseError> {
         if self.next.1 == expected {
             self.advance()
         } else {
-            Err(msg)
+            Err(self.error(kind))
         }
     }
 
-    pub fn parse(&mut self) -> Result<JsonValue, String> {
+    pub fn parse(&mut self) -> Result<JsonValue, ParseError> {
         self.advance()?;
-        self.consume(
-            lexer::JsonTokenType::LeftBrace,
-            "Expected '{' at start of object".to_string(),
-        )?;
+        let value = self.parse_value()?;
 
-        self.parse_object()
+        // RFC 8259 permits any value as a JSON text, but the input must be
+        // fully consumed: anything left over is an error.
+        if self.peek().1 != lexer::JsonTokenType::EOF {
+            return Err(self.error(ErrorKind::TrailingCharacters));
+        }
+
+        Ok(value)
     }
 }
 
@@ -204,6 +267,146 @@ mod test {
         println!("{:?}", result);
     }
 
+    #[test]
+    fn test_encode_roundtrip() {
+        let string = "{\"foo\":\"bar\"}".to_string();
+        let mut parser = super::JsonParser::new_from_string(&string);
+        let value = parser.parse().unwrap();
+        assert_eq!(value.to_string(), string);
+    }
+
+    #[test]
+    fn test_toplevel_values() {
+        use super::{ErrorKind, JsonValue};
+
+        let string = "[1,2,3]".to_string();
+        let mut parser = super::JsonParser::new_from_string(&string);
+        assert_eq!(
+            parser.parse().unwrap(),
+            JsonValue::Array(vec![
+                JsonValue::Integer(1),
+                JsonValue::Integer(2),
+                JsonValue::Integer(3),
+            ])
+        );
+
+        let scalar = "42".to_string();
+        let mut parser = super::JsonParser::new_from_string(&scalar);
+        assert_eq!(parser.parse().unwrap(), JsonValue::Integer(42));
+
+        // Leftover input after a complete value is rejected.
+        let trailing = "{} {}".to_string();
+        let mut parser = super::JsonParser::new_from_string(&trailing);
+        assert_eq!(parser.parse().unwrap_err().kind, ErrorKind::TrailingCharacters);
+    }
+
+    #[test]
+    fn test_parse_error_position() {
+        use super::ErrorKind;
+        let string = "{\"a\":}".to_string();
+        let mut parser = super::JsonParser::new_from_string(&string);
+        let error = parser.parse().unwrap_err();
+        assert_eq!(error.kind, ErrorKind::UnexpectedToken);
+        assert_eq!(error.line, 1);
+    }
+
+    #[test]
+    fn test_decode_struct() {
+        use super::{Decodable, Decoder, DecoderError};
+
+        #[derive(Debug, PartialEq)]
+        struct Point {
+            x: i64,
+            y: i64,
+            label: Option<String>,
+        }
+
+        impl Decodable for Point {
+            fn decode(d: &mut Decoder) -> Result<Point, DecoderError> {
+                d.read_struct("Point", 3, |d| {
+                    Ok(Point {
+                        x: d.read_struct_field("x", 0, Decodable::decode)?,
+                        y: d.read_struct_field("y", 1, Decodable::decode)?,
+                        label: d.read_struct_field("label", 2, Decodable::decode)?,
+                    })
+                })
+            }
+        }
+
+        let point: Point = super::decode("{\"x\":1,\"y\":-2,\"label\":\"p\"}").unwrap();
+        assert_eq!(
+            point,
+            Point {
+                x: 1,
+                y: -2,
+                label: Some("p".to_string()),
+            }
+        );
+
+        // An absent optional field decodes to `None`.
+        let point: Point = super::decode("{\"x\":1,\"y\":-2}").unwrap();
+        assert_eq!(
+            point,
+            Point {
+                x: 1,
+                y: -2,
+                label: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_streaming() {
+        use super::{JsonEvent, StackElement, StreamingParser};
+        let string = "{\"a\":[1,true]}".to_string();
+        let mut parser = StreamingParser::new_from_string(&string);
+        let events: Vec<JsonEvent> = (&mut parser).map(|e| e.unwrap()).collect();
+        assert_eq!(
+            events,
+            vec![
+                JsonEvent::ObjectStart,
+                JsonEvent::Key("a".to_string()),
+                JsonEvent::ArrayStart,
+                JsonEvent::NumberValue(1.0),
+                JsonEvent::BooleanValue(true),
+                JsonEvent::ArrayEnd,
+                JsonEvent::ObjectEnd,
+            ]
+        );
+        assert!(parser.stack().is_empty());
+        let _ = StackElement::Index(0);
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let string = "{\"a\":\"\\n\\\"\\\\\",\"b\":\"\\u00e9\",\"c\":\"\\uD83D\\uDE00\"}".to_string();
+        let mut parser = super::JsonParser::new_from_string(&string);
+        let value = parser.parse().unwrap();
+        if let super::JsonValue::Object(obj) = value {
+            assert_eq!(obj["a"], super::JsonValue::String("\n\"\\".to_string()));
+            assert_eq!(obj["b"], super::JsonValue::String("é".to_string()));
+            assert_eq!(obj["c"], super::JsonValue::String("😀".to_string()));
+        } else {
+            panic!("expected object");
+        }
+    }
+
+    #[test]
+    fn test_numbers() {
+        let string = "{\"a\":-1,\"b\":1.5,\"c\":6.022e23,\"d\":90071992547409920}".to_string();
+        let mut parser = super::JsonParser::new_from_string(&string);
+        let value = parser.parse().unwrap();
+        if let super::JsonValue::Object(obj) = value {
+            assert_eq!(obj["a"], super::JsonValue::Integer(-1));
+            assert_eq!(obj["b"], super::JsonValue::Number(1.5));
+            assert_eq!(obj["c"], super::JsonValue::Number(6.022e23));
+            // An integer above 2^53 is kept as an `Integer`, not turned into a float.
+            assert_eq!(obj["d"], super::JsonValue::Integer(90071992547409920));
+        } else {
+            panic!("expected object");
+        }
+    }
+
     #[test]
     fn test_file() {
         let file = "src/test.json".to_string();
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use super::{JsonParser, JsonValue, ParseError};
+
+/// Errors raised while turning a [`JsonValue`] into a typed value.
+#[derive(Debug, PartialEq)]
+pub enum DecoderError {
+    /// The document could not be parsed in the first place.
+    ParseError(ParseError),
+    /// A struct field that was asked for is absent from the object.
+    MissingFieldError(String),
+    /// A value of the wrong shape was found; holds the expected kind and the
+    /// kind that was actually present.
+    ExpectedError(String, String),
+    /// An error raised by a `Decodable` implementation itself.
+    ApplicationError(String),
+}
+
+/// A type that knows how to build itself from a [`Decoder`].
+pub trait Decodable: Sized {
+    fn decode(d: &mut Decoder) -> Result<Self, DecoderError>;
+}
+
+/// A decoder that walks an already-parsed [`JsonValue`], handing pieces of it
+/// to [`Decodable`] implementations. Values are held on an explicit stack;
+/// each reader consumes the value on top.
+pub struct Decoder {
+    stack: Vec<JsonValue>,
+}
+
+impl Decoder {
+    pub fn new(value: JsonValue) -> Self {
+        Decoder { stack: vec![value] }
+    }
+
+    fn pop(&mut self) -> Result<JsonValue, DecoderError> {
+        self.stack
+            .pop()
+            .ok_or_else(|| DecoderError::ApplicationError("empty decoder stack".to_string()))
+    }
+
+    pub fn read_struct<T, F>(&mut self, _name: &str, _len: usize, f: F) -> Result<T, DecoderError>
+    where
+        F: FnOnce(&mut Decoder) -> Result<T, DecoderError>,
+    {
+        match self.stack.last() {
+            Some(JsonValue::Object(_)) => {}
+            Some(other) => {
+                return Err(DecoderError::ExpectedError(
+                    "Object".to_string(),
+                    describe(other),
+                ))
+            }
+            None => {
+                return Err(DecoderError::ApplicationError(
+                    "empty decoder stack".to_string(),
+                ))
+            }
+        }
+        let result = f(self)?;
+        self.pop()?;
+        Ok(result)
+    }
+
+    pub fn read_struct_field<T, F>(
+        &mut self,
+        name: &str,
+        _idx: usize,
+        f: F,
+    ) -> Result<T, DecoderError>
+    where
+        F: FnOnce(&mut Decoder) -> Result<T, DecoderError>,
+    {
+        let field = match self.stack.last() {
+            Some(JsonValue::Object(obj)) => obj.get(name).cloned(),
+            Some(other) => {
+                return Err(DecoderError::ExpectedError(
+                    "Object".to_string(),
+                    describe(other),
+                ))
+            }
+            None => {
+                return Err(DecoderError::ApplicationError(
+                    "empty decoder stack".to_string(),
+                ))
+            }
+        };
+        match field {
+            Some(value) => {
+                self.stack.push(value);
+                f(self)
+            }
+            None => {
+                // Treat an absent key as `null` so an `Option` field decodes
+                // to `None`; a field that genuinely needs a value surfaces as
+                // a missing field instead.
+                self.stack.push(JsonValue::Nil);
+                f(self).map_err(|_| DecoderError::MissingFieldError(name.to_string()))
+            }
+        }
+    }
+
+    pub fn read_seq<T, F>(&mut self, f: F) -> Result<T, DecoderError>
+    where
+        F: FnOnce(&mut Decoder, usize) -> Result<T, DecoderError>,
+    {
+        match self.pop()? {
+            JsonValue::Array(arr) => {
+                let len = arr.len();
+                for value in arr.into_iter().rev() {
+                    self.stack.push(value);
+                }
+                f(self, len)
+            }
+            other => Err(DecoderError::ExpectedError(
+                "Array".to_string(),
+                describe(&other),
+            )),
+        }
+    }
+
+    pub fn read_seq_elt<T, F>(&mut self, _idx: usize, f: F) -> Result<T, DecoderError>
+    where
+        F: FnOnce(&mut Decoder) -> Result<T, DecoderError>,
+    {
+        f(self)
+    }
+
+    pub fn read_map<T, F>(&mut self, f: F) -> Result<T, DecoderError>
+    where
+        F: FnOnce(&mut Decoder, usize) -> Result<T, DecoderError>,
+    {
+        match self.pop()? {
+            JsonValue::Object(obj) => {
+                let len = obj.len();
+                for (key, value) in obj.into_iter() {
+                    self.stack.push(value);
+                    self.stack.push(JsonValue::String(key));
+                }
+                f(self, len)
+            }
+            other => Err(DecoderError::ExpectedError(
+                "Object".to_string(),
+                describe(&other),
+            )),
+        }
+    }
+
+    pub fn read_map_elt_key<T, F>(&mut self, _idx: usize, f: F) -> Result<T, DecoderError>
+    where
+        F: FnOnce(&mut Decoder) -> Result<T, DecoderError>,
+    {
+        f(self)
+    }
+
+    pub fn read_map_elt_val<T, F>(&mut self, _idx: usize, f: F) -> Result<T, DecoderError>
+    where
+        F: FnOnce(&mut Decoder) -> Result<T, DecoderError>,
+    {
+        f(self)
+    }
+
+    /// Read an optional value. `f` is invoked with `false` when the value is
+    /// `null` (already consumed) and `true` when a value is present.
+    pub fn read_option<T, F>(&mut self, f: F) -> Result<T, DecoderError>
+    where
+        F: FnOnce(&mut Decoder, bool) -> Result<T, DecoderError>,
+    {
+        match self.stack.last() {
+            Some(JsonValue::Nil) => {
+                self.pop()?;
+                f(self, false)
+            }
+            Some(_) => f(self, true),
+            None => Err(DecoderError::ApplicationError(
+                "empty decoder stack".to_string(),
+            )),
+        }
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, DecoderError> {
+        match self.pop()? {
+            JsonValue::Boolean(b) => Ok(b),
+            other => Err(DecoderError::ExpectedError(
+                "Boolean".to_string(),
+                describe(&other),
+            )),
+        }
+    }
+
+    pub fn read_str(&mut self) -> Result<String, DecoderError> {
+        match self.pop()? {
+            JsonValue::String(s) => Ok(s),
+            other => Err(DecoderError::ExpectedError(
+                "String".to_string(),
+                describe(&other),
+            )),
+        }
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64, DecoderError> {
+        match self.pop()? {
+            JsonValue::Integer(i) => Ok(i),
+            JsonValue::Number(n) => Ok(n as i64),
+            other => Err(DecoderError::ExpectedError(
+                "Integer".to_string(),
+                describe(&other),
+            )),
+        }
+    }
+
+    pub fn read_f64(&mut self) -> Result<f64, DecoderError> {
+        match self.pop()? {
+            JsonValue::Number(n) => Ok(n),
+            JsonValue::Integer(i) => Ok(i as f64),
+            other => Err(DecoderError::ExpectedError(
+                "Number".to_string(),
+                describe(&other),
+            )),
+        }
+    }
+}
+
+fn describe(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(_) => "String",
+        JsonValue::Integer(_) => "Integer",
+        JsonValue::Number(_) => "Number",
+        JsonValue::Object(_) => "Object",
+        JsonValue::Array(_) => "Array",
+        JsonValue::Boolean(_) => "Boolean",
+        JsonValue::Nil => "Null",
+    }
+    .to_string()
+}
+
+impl Decodable for String {
+    fn decode(d: &mut Decoder) -> Result<String, DecoderError> {
+        d.read_str()
+    }
+}
+
+impl Decodable for bool {
+    fn decode(d: &mut Decoder) -> Result<bool, DecoderError> {
+        d.read_bool()
+    }
+}
+
+macro_rules! decodable_int {
+    ($($t:ty),*) => {
+        $(impl Decodable for $t {
+            fn decode(d: &mut Decoder) -> Result<$t, DecoderError> {
+                Ok(d.read_i64()? as $t)
+            }
+        })*
+    };
+}
+
+macro_rules! decodable_float {
+    ($($t:ty),*) => {
+        $(impl Decodable for $t {
+            fn decode(d: &mut Decoder) -> Result<$t, DecoderError> {
+                Ok(d.read_f64()? as $t)
+            }
+        })*
+    };
+}
+
+decodable_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+decodable_float!(f32, f64);
+
+impl<T: Decodable> Decodable for Option<T> {
+    fn decode(d: &mut Decoder) -> Result<Option<T>, DecoderError> {
+        d.read_option(|d, present| {
+            if present {
+                Ok(Some(Decodable::decode(d)?))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+}
+
+impl<T: Decodable> Decodable for Vec<T> {
+    fn decode(d: &mut Decoder) -> Result<Vec<T>, DecoderError> {
+        d.read_seq(|d, len| {
+            let mut out = Vec::with_capacity(len);
+            for i in 0..len {
+                out.push(d.read_seq_elt(i, Decodable::decode)?);
+            }
+            Ok(out)
+        })
+    }
+}
+
+impl<T: Decodable> Decodable for HashMap<String, T> {
+    fn decode(d: &mut Decoder) -> Result<HashMap<String, T>, DecoderError> {
+        d.read_map(|d, len| {
+            let mut out = HashMap::with_capacity(len);
+            for i in 0..len {
+                let key = d.read_map_elt_key(i, Decodable::decode)?;
+                let value = d.read_map_elt_val(i, Decodable::decode)?;
+                out.insert(key, value);
+            }
+            Ok(out)
+        })
+    }
+}
+
+/// Parse `json` and decode it into any [`Decodable`] type.
+pub fn decode<T: Decodable>(json: &str) -> Result<T, DecoderError> {
+    let source = json.to_string();
+    let mut parser = JsonParser::new_from_string(&source);
+    let value = parser.parse().map_err(DecoderError::ParseError)?;
+    let mut decoder = Decoder::new(value);
+    Decodable::decode(&mut decoder)
+}
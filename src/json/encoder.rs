@@ -0,0 +1,124 @@
+use std::fmt;
+
+use super::JsonValue;
+
+impl fmt::Display for JsonValue {
+    /// Render the compact JSON representation with no superfluous whitespace.
+    /// The `to_string` method is supplied by the blanket `ToString` impl.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = String::new();
+        self.encode(&mut out);
+        f.write_str(&out)
+    }
+}
+
+impl JsonValue {
+    /// Serialize the value to an indented, human-readable representation.
+    /// `indent` is the number of spaces to add per nesting level.
+    pub fn to_pretty_string(&self, indent: usize) -> String {
+        let mut out = String::new();
+        self.encode_pretty(&mut out, indent, 0);
+        out
+    }
+
+    fn encode(&self, out: &mut String) {
+        match self {
+            JsonValue::String(s) => encode_string(s, out),
+            JsonValue::Integer(i) => out.push_str(&i.to_string()),
+            JsonValue::Number(n) => out.push_str(&encode_number(*n)),
+            JsonValue::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+            JsonValue::Nil => out.push_str("null"),
+            JsonValue::Array(arr) => {
+                out.push('[');
+                for (i, value) in arr.iter().enumerate() {
+                    if i != 0 {
+                        out.push(',');
+                    }
+                    value.encode(out);
+                }
+                out.push(']');
+            }
+            JsonValue::Object(obj) => {
+                out.push('{');
+                for (i, (key, value)) in obj.iter().enumerate() {
+                    if i != 0 {
+                        out.push(',');
+                    }
+                    encode_string(key, out);
+                    out.push(':');
+                    value.encode(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+
+    fn encode_pretty(&self, out: &mut String, indent: usize, level: usize) {
+        match self {
+            JsonValue::Array(arr) if !arr.is_empty() => {
+                out.push('[');
+                for (i, value) in arr.iter().enumerate() {
+                    if i != 0 {
+                        out.push(',');
+                    }
+                    push_newline_indent(out, indent, level + 1);
+                    value.encode_pretty(out, indent, level + 1);
+                }
+                push_newline_indent(out, indent, level);
+                out.push(']');
+            }
+            JsonValue::Object(obj) if !obj.is_empty() => {
+                out.push('{');
+                for (i, (key, value)) in obj.iter().enumerate() {
+                    if i != 0 {
+                        out.push(',');
+                    }
+                    push_newline_indent(out, indent, level + 1);
+                    encode_string(key, out);
+                    out.push_str(": ");
+                    value.encode_pretty(out, indent, level + 1);
+                }
+                push_newline_indent(out, indent, level);
+                out.push('}');
+            }
+            _ => self.encode(out),
+        }
+    }
+}
+
+fn push_newline_indent(out: &mut String, indent: usize, level: usize) {
+    out.push('\n');
+    for _ in 0..(indent * level) {
+        out.push(' ');
+    }
+}
+
+fn encode_string(s: &str, out: &mut String) {
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\x08' => out.push_str("\\b"),
+            '\x0c' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn encode_number(n: super::JsonNumberType) -> String {
+    // `{}` formatting drops the `.0` from whole-valued floats, which the
+    // parser would then read back as an `Integer`; force a fractional form
+    // so the `Number` variant round-trips.
+    let s = n.to_string();
+    if s.contains(['.', 'e', 'E']) {
+        s
+    } else {
+        s + ".0"
+    }
+}
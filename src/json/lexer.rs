@@ -1,6 +1,7 @@
 use std::fs;
 use std::io::{Bytes, Read};
 
+use super::{ErrorKind, ParseError, Position};
 use crate::utf8;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -20,11 +21,11 @@ pub enum JsonTokenType {
 }
 
 #[derive(Debug)]
-pub struct Token(pub String, pub JsonTokenType);
+pub struct Token(pub String, pub JsonTokenType, pub Position);
 
 impl Token {
-    pub fn new(s: String, t: JsonTokenType) -> Self {
-        Token(s, t)
+    pub fn new(s: String, t: JsonTokenType, pos: Position) -> Self {
+        Token(s, t, pos)
     }
 }
 
@@ -37,6 +38,8 @@ pub struct JsonLexer<'a> {
     file: ContentType<'a>,
     putback: char,
     has_putback: bool,
+    line: usize,
+    col: usize,
 }
 
 impl<'a> JsonLexer<'a> {
@@ -45,6 +48,8 @@ impl<'a> JsonLexer<'a> {
             file: ContentType::File(fs::File::open(filename).unwrap().bytes()),
             putback: '\0',
             has_putback: false,
+            line: 1,
+            col: 0,
         }
     }
 
@@ -53,34 +58,59 @@ impl<'a> JsonLexer<'a> {
             file: ContentType::<'a>::String(s.bytes()),
             putback: '\0',
             has_putback: false,
+            line: 1,
+            col: 0,
         }
     }
 
-    fn next_char(&mut self) -> Result<char, &'static str> {
+    /// The position of the character most recently produced by `next_char`.
+    pub fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            col: self.col,
+        }
+    }
+
+    fn error(&self, kind: ErrorKind) -> ParseError {
+        ParseError::new(self.position(), kind)
+    }
+
+    fn advance_position(&mut self, ch: char) {
+        if ch == '\n' {
+            self.line += 1;
+            self.col = 0;
+        } else {
+            self.col += 1;
+        }
+    }
+
+    fn next_char(&mut self) -> Result<char, ParseError> {
         if self.has_putback {
             self.has_putback = false;
             Ok(self.putback)
         } else {
-            match self.file {
+            let ch = match self.file {
                 ContentType::File(ref mut f) => match f.next() {
                     Some(Ok(c)) => {
                         match utf8::next_codepoint_head(f, c, |file| file.next().unwrap().unwrap())
                         {
-                            Some(h) => Ok(h),
-                            None => Err("Invalid UTF-8"),
+                            Some(h) => h,
+                            None => return Err(self.error(ErrorKind::UnexpectedToken)),
                         }
                     }
-                    Some(Err(_)) => Err("Error reading file"),
-                    None => Ok('\0'),
+                    Some(Err(_)) => return Err(self.error(ErrorKind::UnexpectedToken)),
+                    None => '\0',
                 },
                 ContentType::String(ref mut s) => match s.next() {
                     Some(c) => match utf8::next_codepoint_head(s, c, |str| str.next().unwrap()) {
-                        Some(h) => Ok(h),
-                        None => Err("Invalid UTF-8"),
+                        Some(h) => h,
+                        None => return Err(self.error(ErrorKind::UnexpectedToken)),
                     },
-                    None => Ok('\0'),
+                    None => '\0',
                 },
-            }
+            };
+            self.advance_position(ch);
+            Ok(ch)
         }
     }
 
@@ -117,47 +147,128 @@ impl<'a> JsonLexer<'a> {
         self.has_putback = true;
     }
 
-    pub fn next_token(&mut self) -> Result<Token, &'static str> {
+    /// Decode the escape sequence following a backslash inside a string.
+    /// Handles the single-character escapes as well as `\uXXXX`, combining
+    /// UTF-16 surrogate pairs into a single code point.
+    fn scan_escape(&mut self) -> Result<char, ParseError> {
+        match self.next_char()? {
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            '/' => Ok('/'),
+            'b' => Ok('\u{08}'),
+            'f' => Ok('\u{0c}'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            'u' => {
+                let hi = self.read_hex4()?;
+                let codepoint = if (0xD800..=0xDBFF).contains(&hi) {
+                    if !(self.try_match_char('\\') && self.try_match_char('u')) {
+                        return Err(self.error(ErrorKind::InvalidEscape));
+                    }
+                    let lo = self.read_hex4()?;
+                    if !(0xDC00..=0xDFFF).contains(&lo) {
+                        return Err(self.error(ErrorKind::InvalidEscape));
+                    }
+                    0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00)
+                } else if (0xDC00..=0xDFFF).contains(&hi) {
+                    return Err(self.error(ErrorKind::InvalidEscape));
+                } else {
+                    hi
+                };
+                char::from_u32(codepoint).ok_or_else(|| self.error(ErrorKind::InvalidEscape))
+            }
+            _ => Err(self.error(ErrorKind::InvalidEscape)),
+        }
+    }
+
+    fn read_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            match self.next_char()?.to_digit(16) {
+                Some(digit) => value = value * 16 + digit,
+                None => return Err(self.error(ErrorKind::InvalidEscape)),
+            }
+        }
+        Ok(value)
+    }
+
+    pub fn next_token(&mut self) -> Result<Token, ParseError> {
         let mut ch = self.next_char()?;
         // while ch.is_whitespace() {
         //     ch = self.next_char()?;
         // }
+        let start = self.position();
         match ch {
-            '{' => Ok(Token(String::from("{"), JsonTokenType::LeftBrace)),
-            '}' => Ok(Token(String::from("}"), JsonTokenType::RightBrace)),
-            '[' => Ok(Token(String::from("["), JsonTokenType::LeftBracket)),
-            ']' => Ok(Token(String::from("]"), JsonTokenType::RightBracket)),
-            ',' => Ok(Token(String::from(","), JsonTokenType::Comma)),
-            ':' => Ok(Token(String::from(":"), JsonTokenType::Colon)),
+            '{' => Ok(Token(String::from("{"), JsonTokenType::LeftBrace, start)),
+            '}' => Ok(Token(String::from("}"), JsonTokenType::RightBrace, start)),
+            '[' => Ok(Token(String::from("["), JsonTokenType::LeftBracket, start)),
+            ']' => Ok(Token(String::from("]"), JsonTokenType::RightBracket, start)),
+            ',' => Ok(Token(String::from(","), JsonTokenType::Comma, start)),
+            ':' => Ok(Token(String::from(":"), JsonTokenType::Colon, start)),
             '"' => {
                 let mut s = String::new();
                 loop {
                     ch = self.next_char()?;
-                    if ch == '"' {
-                        break;
+                    match ch {
+                        '"' => break,
+                        '\0' => return Err(self.error(ErrorKind::EofWhileParsing)),
+                        '\\' => s.push(self.scan_escape()?),
+                        _ => s.push(ch),
                     }
-                    s.push(ch);
                 }
-                Ok(Token(s, JsonTokenType::String))
+                Ok(Token(s, JsonTokenType::String, start))
             }
-            '0'..='9' => {
-                let mut s = String::from(ch.to_string());
+            '-' | '0'..='9' => {
+                let mut s = String::new();
+                s.push(ch);
+
+                // integer part
                 loop {
-                    s.push(ch);
                     ch = self.next_char()?;
-                    if !ch.is_ascii_digit() {
+                    if ch.is_ascii_digit() {
+                        s.push(ch);
+                    } else {
                         break;
                     }
                 }
+
+                // fractional part
+                if ch == '.' {
+                    s.push(ch);
+                    loop {
+                        ch = self.next_char()?;
+                        if ch.is_ascii_digit() {
+                            s.push(ch);
+                        } else {
+                            break;
+                        }
+                    }
+                }
+
+                // exponent part
+                if ch == 'e' || ch == 'E' {
+                    s.push(ch);
+                    ch = self.next_char()?;
+                    if ch == '+' || ch == '-' {
+                        s.push(ch);
+                        ch = self.next_char()?;
+                    }
+                    while ch.is_ascii_digit() {
+                        s.push(ch);
+                        ch = self.next_char()?;
+                    }
+                }
+
                 self.putback(ch);
-                Ok(Token(s, JsonTokenType::Number))
+                Ok(Token(s, JsonTokenType::Number, start))
             }
             't' => {
                 if self.try_match_char('r') && self.try_match_char('u') && self.try_match_char('e')
                 {
-                    Ok(Token(String::from("true"), JsonTokenType::Boolean))
+                    Ok(Token(String::from("true"), JsonTokenType::Boolean, start))
                 } else {
-                    Err("expected true")
+                    Err(self.error(ErrorKind::UnexpectedToken))
                 }
             }
             'f' => {
@@ -166,28 +277,22 @@ impl<'a> JsonLexer<'a> {
                     && self.try_match_char('s')
                     && self.try_match_char('e')
                 {
-                    Ok(Token(String::from("false"), JsonTokenType::Boolean))
+                    Ok(Token(String::from("false"), JsonTokenType::Boolean, start))
                 } else {
-                    Err("expected false")
+                    Err(self.error(ErrorKind::UnexpectedToken))
                 }
             }
             'n' => {
                 if self.try_match_char('u') && self.try_match_char('l') && self.try_match_char('l')
                 {
-                    Ok(Token(String::from("null"), JsonTokenType::Null))
+                    Ok(Token(String::from("null"), JsonTokenType::Null, start))
                 } else {
-                    Err("expected null")
+                    Err(self.error(ErrorKind::UnexpectedToken))
                 }
             }
-            '\0' => Ok(Token(String::from(""), JsonTokenType::EOF)),
+            '\0' => Ok(Token(String::from(""), JsonTokenType::EOF, start)),
             _ if ch.is_whitespace() => self.next_token(),
-            _ => {
-                if ch.is_ascii_punctuation() {
-                    Err("unexpected punctuation")
-                } else {
-                    Err("unexpected character")
-                }
-            }
+            _ => Err(self.error(ErrorKind::UnexpectedToken)),
         }
     }
 }
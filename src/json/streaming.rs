@@ -0,0 +1,268 @@
+use super::lexer::{self, JsonLexer, JsonTokenType};
+use super::{ErrorKind, JsonNumberType, ParseError};
+
+/// An event yielded by the [`StreamingParser`] as it walks the document.
+#[derive(Debug, PartialEq)]
+pub enum JsonEvent {
+    ObjectStart,
+    ObjectEnd,
+    ArrayStart,
+    ArrayEnd,
+    Key(String),
+    StringValue(String),
+    NumberValue(JsonNumberType),
+    BooleanValue(bool),
+    NullValue,
+}
+
+/// One level of the path to the value most recently emitted: an array index
+/// or an object key.
+#[derive(Debug, PartialEq, Clone)]
+pub enum StackElement {
+    Index(usize),
+    Key(String),
+}
+
+enum Container {
+    Array(usize),
+    Object(Option<String>),
+}
+
+enum Mode {
+    Root,
+    ArrayStart,
+    ArrayValue,
+    ArrayComma,
+    ObjectStart,
+    ObjectKey,
+    ObjectColon,
+    ObjectValue,
+    ObjectComma,
+    Done,
+}
+
+/// A pull-based parser that yields [`JsonEvent`]s over the same [`JsonLexer`]
+/// as [`JsonParser`](super::JsonParser), without materializing the document.
+/// Nesting is tracked with an explicit stack instead of recursion.
+pub struct StreamingParser<'a> {
+    lexer: JsonLexer<'a>,
+    stack: Vec<Container>,
+    mode: Mode,
+    errored: bool,
+}
+
+impl<'a> StreamingParser<'a> {
+    pub fn new(filename: String) -> Self {
+        StreamingParser {
+            lexer: JsonLexer::new(filename),
+            stack: Vec::new(),
+            mode: Mode::Root,
+            errored: false,
+        }
+    }
+
+    pub fn new_from_string(s: &'a String) -> Self {
+        StreamingParser {
+            lexer: JsonLexer::new_from_string(s),
+            stack: Vec::new(),
+            mode: Mode::Root,
+            errored: false,
+        }
+    }
+
+    /// The path to the value produced by the most recent event, from the
+    /// document root downwards. Right after `ArrayStart` the path already ends
+    /// in `Index(0)`, the slot of the new array's first element.
+    pub fn stack(&self) -> Vec<StackElement> {
+        self.stack
+            .iter()
+            .filter_map(|c| match c {
+                Container::Array(i) => Some(StackElement::Index(*i)),
+                Container::Object(Some(k)) => Some(StackElement::Key(k.clone())),
+                Container::Object(None) => None,
+            })
+            .collect()
+    }
+
+    fn token(&mut self) -> Result<lexer::Token, ParseError> {
+        self.lexer.next_token()
+    }
+
+    fn error(&self, kind: ErrorKind) -> ParseError {
+        ParseError::new(self.lexer.position(), kind)
+    }
+
+    fn set_key(&mut self, key: String) {
+        if let Some(Container::Object(slot)) = self.stack.last_mut() {
+            *slot = Some(key);
+        }
+    }
+
+    /// Pick the state to resume in after a value at the current nesting level
+    /// has been fully emitted.
+    fn successor(&mut self) {
+        self.mode = match self.stack.last() {
+            Some(Container::Array(_)) => Mode::ArrayComma,
+            Some(Container::Object(_)) => Mode::ObjectComma,
+            None => Mode::Done,
+        };
+    }
+
+    fn close_array(&mut self) -> JsonEvent {
+        self.stack.pop();
+        self.successor();
+        JsonEvent::ArrayEnd
+    }
+
+    fn close_object(&mut self) -> JsonEvent {
+        self.stack.pop();
+        self.successor();
+        JsonEvent::ObjectEnd
+    }
+
+    /// Emit the event beginning with `tok`, pushing a container state for
+    /// compound values and selecting the successor state for scalars.
+    fn value(&mut self, tok: lexer::Token) -> Result<JsonEvent, ParseError> {
+        let pos = tok.2;
+        match tok.1 {
+            JsonTokenType::LeftBrace => {
+                self.stack.push(Container::Object(None));
+                self.mode = Mode::ObjectStart;
+                Ok(JsonEvent::ObjectStart)
+            }
+            JsonTokenType::LeftBracket => {
+                self.stack.push(Container::Array(0));
+                self.mode = Mode::ArrayStart;
+                Ok(JsonEvent::ArrayStart)
+            }
+            JsonTokenType::String => {
+                self.successor();
+                Ok(JsonEvent::StringValue(tok.0))
+            }
+            JsonTokenType::Number => {
+                let n = tok
+                    .0
+                    .parse()
+                    .map_err(|_| ParseError::new(pos, ErrorKind::InvalidNumber))?;
+                self.successor();
+                Ok(JsonEvent::NumberValue(n))
+            }
+            JsonTokenType::Boolean => {
+                let b = tok
+                    .0
+                    .parse()
+                    .map_err(|_| ParseError::new(pos, ErrorKind::UnexpectedToken))?;
+                self.successor();
+                Ok(JsonEvent::BooleanValue(b))
+            }
+            JsonTokenType::Null => {
+                self.successor();
+                Ok(JsonEvent::NullValue)
+            }
+            _ => Err(ParseError::new(pos, ErrorKind::UnexpectedToken)),
+        }
+    }
+
+    fn step(&mut self) -> Result<Option<JsonEvent>, ParseError> {
+        loop {
+            match self.mode {
+                Mode::Done => return Ok(None),
+                Mode::Root => {
+                    let tok = self.token()?;
+                    if tok.1 == JsonTokenType::EOF {
+                        self.mode = Mode::Done;
+                        return Ok(None);
+                    }
+                    return self.value(tok).map(Some);
+                }
+                Mode::ArrayStart => {
+                    let tok = self.token()?;
+                    if tok.1 == JsonTokenType::RightBracket {
+                        return Ok(Some(self.close_array()));
+                    }
+                    return self.value(tok).map(Some);
+                }
+                Mode::ArrayValue => {
+                    let tok = self.token()?;
+                    return self.value(tok).map(Some);
+                }
+                Mode::ArrayComma => {
+                    let tok = self.token()?;
+                    match tok.1 {
+                        JsonTokenType::Comma => {
+                            if let Some(Container::Array(i)) = self.stack.last_mut() {
+                                *i += 1;
+                            }
+                            self.mode = Mode::ArrayValue;
+                        }
+                        JsonTokenType::RightBracket => return Ok(Some(self.close_array())),
+                        _ => return Err(self.error(ErrorKind::UnexpectedToken)),
+                    }
+                }
+                Mode::ObjectStart => {
+                    let tok = self.token()?;
+                    match tok.1 {
+                        JsonTokenType::RightBrace => return Ok(Some(self.close_object())),
+                        JsonTokenType::String => {
+                            let key = tok.0;
+                            self.set_key(key.clone());
+                            self.mode = Mode::ObjectColon;
+                            return Ok(Some(JsonEvent::Key(key)));
+                        }
+                        _ => return Err(self.error(ErrorKind::UnexpectedToken)),
+                    }
+                }
+                Mode::ObjectKey => {
+                    let tok = self.token()?;
+                    match tok.1 {
+                        JsonTokenType::String => {
+                            let key = tok.0;
+                            self.set_key(key.clone());
+                            self.mode = Mode::ObjectColon;
+                            return Ok(Some(JsonEvent::Key(key)));
+                        }
+                        _ => return Err(self.error(ErrorKind::UnexpectedToken)),
+                    }
+                }
+                Mode::ObjectColon => {
+                    let tok = self.token()?;
+                    if tok.1 == JsonTokenType::Colon {
+                        self.mode = Mode::ObjectValue;
+                    } else {
+                        return Err(self.error(ErrorKind::UnexpectedToken));
+                    }
+                }
+                Mode::ObjectValue => {
+                    let tok = self.token()?;
+                    return self.value(tok).map(Some);
+                }
+                Mode::ObjectComma => {
+                    let tok = self.token()?;
+                    match tok.1 {
+                        JsonTokenType::Comma => self.mode = Mode::ObjectKey,
+                        JsonTokenType::RightBrace => return Ok(Some(self.close_object())),
+                        _ => return Err(self.error(ErrorKind::UnexpectedToken)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for StreamingParser<'a> {
+    type Item = Result<JsonEvent, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.errored {
+            return None;
+        }
+        match self.step() {
+            Ok(None) => None,
+            Ok(Some(event)) => Some(Ok(event)),
+            Err(e) => {
+                self.errored = true;
+                Some(Err(e))
+            }
+        }
+    }
+}